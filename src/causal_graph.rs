@@ -0,0 +1,189 @@
+use std::collections::{HashMap, HashSet};
+
+/// An operation id, `(user, seq)`.
+pub type OpId = (u16, u32);
+
+/// Tracks which operations are waiting on others,
+/// and hands them out once everything they depend on is available.
+/// Each op keeps a count of unsatisfied dependencies,
+/// so marking an op available only touches the ops waiting on it.
+/// Ops can be delivered more than once, each is released at most once.
+#[derive(Debug, Default)]
+pub struct CausalGraph {
+    available:  HashSet<OpId>,
+    waiting:    HashMap<OpId, usize>,
+    dependents: HashMap<OpId, Vec<OpId>>,
+    ready:      Vec<OpId>,
+    // everything ever pushed onto `ready`, so redelivered ops aren't queued twice
+    queued:     HashSet<OpId>,
+}
+
+impl CausalGraph {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds an op along with the ops it depends on.
+    /// If all of them are already available, it's ready straight away.
+    /// Ops that are already available, waiting or ready are ignored.
+    pub fn add_op(&mut self, id: OpId, mut deps: Vec<OpId>) {
+        if self.available.contains(&id)
+        || self.waiting.contains_key(&id)
+        || self.queued.contains(&id) { return; }
+
+        deps.sort_unstable();
+        deps.dedup();
+        deps.retain(|dep| !self.available.contains(dep));
+
+        if deps.is_empty() {
+            self.release(id);
+            return;
+        }
+
+        self.waiting.insert(id, deps.len());
+        for dep in deps {
+            self.dependents.entry(dep).or_default().push(id);
+        }
+    }
+
+    fn release(&mut self, id: OpId) {
+        if self.queued.insert(id) { self.ready.push(id); }
+    }
+
+    /// Records that an op has been applied,
+    /// releasing any ops for which it was the last missing dependency.
+    /// An op marked available while still waiting is never released.
+    pub fn mark_available(&mut self, id: OpId) {
+        if !self.available.insert(id) { return; }
+        self.waiting.remove(&id);
+
+        for dependent in self.dependents.remove(&id).unwrap_or_default() {
+            let count = match self.waiting.get_mut(&dependent) {
+                Some(count) => count,
+                None => continue,
+            };
+            *count -= 1;
+            if *count == 0 {
+                self.waiting.remove(&dependent);
+                self.release(dependent);
+            }
+        }
+    }
+
+    /// Takes the ops that became ready since the last call,
+    /// skipping any that were marked available in the meantime.
+    /// The caller should apply them and then `mark_available` each one,
+    /// which may make more ops ready.
+    pub fn ready_ops(&mut self) -> Vec<OpId> {
+        let mut ready = std::mem::take(&mut self.ready);
+        ready.retain(|id| !self.available.contains(id));
+        ready
+    }
+
+    /// Number of ops still waiting on a dependency.
+    pub fn pending(&self) -> usize {
+        self.waiting.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+
+    /// Drains `ready_ops` until nothing is left, applying each batch
+    /// in a random order, and returns the order ops were applied in.
+    fn apply_ready(graph: &mut CausalGraph, rng: &mut Rng, applied: &mut Vec<OpId>) {
+        loop {
+            let mut ready = graph.ready_ops();
+            if ready.is_empty() { return; }
+            rng.shuffle(&mut ready);
+            for id in ready {
+                applied.push(id);
+                graph.mark_available(id);
+            }
+        }
+    }
+
+    /// Random DAGs delivered in random order, some ops more than once,
+    /// must come out in an order where every op follows its deps.
+    #[test]
+    fn any_ready_order_is_causal() {
+        let mut rng = Rng::new(0x510e527fade682d1);
+        for _ in 0..500 {
+            let n = rng.below(40) + 1;
+            let ids: Vec<OpId> = (0..n).map(|i| ((i % 3) as u16, i as u32)).collect();
+            let deps: HashMap<OpId, Vec<OpId>> = (0..n)
+                .map(|i| {
+                    let count = if i == 0 { 0 } else { rng.below(3) };
+                    (ids[i], (0..count).map(|_| ids[rng.below(i)]).collect())
+                })
+                .collect();
+
+            let mut deliveries = ids.clone();
+            for _ in 0..rng.below(10) { deliveries.push(ids[rng.below(n)]); }
+            rng.shuffle(&mut deliveries);
+
+            let mut graph = CausalGraph::new();
+            let mut applied = vec![];
+            for id in deliveries {
+                graph.add_op(id, deps[&id].clone());
+                apply_ready(&mut graph, &mut rng, &mut applied);
+            }
+
+            assert_eq!(graph.pending(), 0);
+            let position: HashMap<_, _> = applied.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+            assert_eq!(position.len(), applied.len(), "op applied twice");
+            assert_eq!(applied.len(), n);
+            for (id, deps) in deps.iter() {
+                for dep in deps {
+                    assert!(position[dep] < position[id]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_add_op() {
+        let mut graph = CausalGraph::new();
+        graph.add_op((0, 2), vec![(0, 1)]);
+        graph.add_op((0, 2), vec![(0, 1)]);
+        graph.mark_available((0, 1));
+        assert_eq!(graph.ready_ops(), vec![(0, 2)]);
+        assert_eq!(graph.pending(), 0);
+
+        // redelivered after release, before and after being applied
+        graph.add_op((0, 2), vec![(0, 1)]);
+        assert_eq!(graph.ready_ops(), vec![]);
+        graph.mark_available((0, 2));
+        graph.add_op((0, 2), vec![(0, 1)]);
+        assert_eq!(graph.ready_ops(), vec![]);
+    }
+
+    #[test]
+    fn available_while_waiting_is_not_released() {
+        let mut graph = CausalGraph::new();
+        graph.add_op((0, 2), vec![(0, 1)]);
+        graph.mark_available((0, 2));
+        assert_eq!(graph.pending(), 0);
+        graph.mark_available((0, 1));
+        assert_eq!(graph.ready_ops(), vec![]);
+    }
+
+    #[test]
+    fn available_before_drained_is_skipped() {
+        let mut graph = CausalGraph::new();
+        graph.add_op((0, 1), vec![]);
+        graph.add_op((1, 1), vec![]);
+        graph.mark_available((0, 1));
+        assert_eq!(graph.ready_ops(), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn add_op_of_available_id_is_ignored() {
+        let mut graph = CausalGraph::new();
+        graph.mark_available((0, 1));
+        graph.add_op((0, 1), vec![]);
+        assert_eq!(graph.ready_ops(), vec![]);
+    }
+}
@@ -0,0 +1,219 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// A single logical clock, totally ordering the events one replica sees.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LamportClock {
+    time: u64,
+}
+
+impl LamportClock {
+    pub fn new() -> Self {
+        LamportClock { time: 0 }
+    }
+
+    /// Advances the clock for a local event and returns the new time.
+    pub fn tick(&mut self) -> u64 {
+        self.time += 1;
+        self.time
+    }
+
+    /// Catches up with a timestamp received from another replica,
+    /// then ticks, so the receive is ordered after the send.
+    pub fn witness(&mut self, received: u64) -> u64 {
+        self.time = self.time.max(received);
+        self.tick()
+    }
+
+    pub fn current(&self) -> u64 {
+        self.time
+    }
+}
+
+/// One counter per user, partially ordering events across replicas.
+/// Users that were never ticked are implicitly at zero and aren't stored,
+/// so two clocks that saw the same events compare equal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VectorClock {
+    entries: BTreeMap<u16, u64>,
+}
+
+impl VectorClock {
+    pub fn new() -> Self {
+        VectorClock { entries: BTreeMap::new() }
+    }
+
+    pub fn get(&self, user: u16) -> u64 {
+        self.entries.get(&user).copied().unwrap_or(0)
+    }
+
+    /// Advances `user`'s counter and returns its new value.
+    pub fn tick(&mut self, user: u16) -> u64 {
+        let time = self.entries.entry(user).or_insert(0);
+        *time += 1;
+        *time
+    }
+
+    /// Merges in everything `other` has seen, taking the max per user.
+    pub fn witness(&mut self, other: &VectorClock) {
+        for (user, time) in other.entries.iter() {
+            let entry = self.entries.entry(*user).or_insert(0);
+            *entry = (*entry).max(*time);
+        }
+    }
+
+    /// True if every entry is `<=` the matching entry in `other`.
+    /// This is not strict, so a clock happens before itself.
+    pub fn happens_before(&self, other: &VectorClock) -> bool {
+        self.entries.iter().all(|(user, time)| *time <= other.get(*user))
+    }
+
+    /// True if every entry is `>=` the matching entry in `other`.
+    pub fn dominates(&self, other: &VectorClock) -> bool {
+        other.happens_before(self)
+    }
+
+    /// True if neither clock has seen everything the other has.
+    pub fn concurrent(&self, other: &VectorClock) -> bool {
+        !self.happens_before(other) && !other.happens_before(self)
+    }
+}
+
+impl PartialOrd for VectorClock {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.happens_before(other), other.happens_before(self)) {
+            (true, true)   => Some(Ordering::Equal),
+            (true, false)  => Some(Ordering::Less),
+            (false, true)  => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+
+    const USERS: usize = 4;
+
+    /// A plain per-user array kept alongside each clock,
+    /// so the relations can be checked without going through `VectorClock`.
+    type Model = [u64; USERS];
+
+    /// Ticks a few users a few times, sometimes starting from `base`
+    /// so that ordered pairs come up as well as concurrent ones.
+    fn random_clock(rng: &mut Rng, base: &(VectorClock, Model)) -> (VectorClock, Model) {
+        let (mut clock, mut model) = if rng.below(2) == 0 {
+            base.clone()
+        } else {
+            (VectorClock::new(), [0; USERS])
+        };
+        for _ in 0..rng.below(6) {
+            let user = rng.below(USERS);
+            clock.tick(user as u16);
+            model[user] += 1;
+        }
+        (clock, model)
+    }
+
+    fn empty() -> (VectorClock, Model) {
+        (VectorClock::new(), [0; USERS])
+    }
+
+    fn model_le(a: &Model, b: &Model) -> bool {
+        a.iter().zip(b.iter()).all(|(a, b)| a <= b)
+    }
+
+    #[test]
+    fn lamport_tick_and_witness() {
+        let mut clock = LamportClock::new();
+        assert_eq!(clock.current(), 0);
+        assert_eq!(clock.tick(), 1);
+        assert_eq!(clock.witness(10), 11);
+        assert_eq!(clock.witness(3), 12);
+        assert_eq!(clock.current(), 12);
+    }
+
+    #[test]
+    fn vector_tick_and_get() {
+        let mut clock = VectorClock::new();
+        assert_eq!(clock.get(3), 0);
+        assert_eq!(clock.tick(3), 1);
+        assert_eq!(clock.tick(3), 2);
+        assert_eq!(clock.get(3), 2);
+        assert_eq!(clock.get(0), 0);
+    }
+
+    #[test]
+    fn relations_match_model() {
+        let mut rng = Rng::new(0xda942042e4dd58b5);
+        let (mut ordered, mut concurrent) = (0, 0);
+
+        for _ in 0..5000 {
+            let (a, a_model) = random_clock(&mut rng, &empty());
+            let (b, b_model) = random_clock(&mut rng, &(a.clone(), a_model));
+
+            let le = model_le(&a_model, &b_model);
+            let ge = model_le(&b_model, &a_model);
+            let expected = match (le, ge) {
+                (true, true)   => Some(Ordering::Equal),
+                (true, false)  => Some(Ordering::Less),
+                (false, true)  => Some(Ordering::Greater),
+                (false, false) => None,
+            };
+
+            assert_eq!(a.happens_before(&b), le);
+            assert_eq!(b.happens_before(&a), ge);
+            assert_eq!(a.dominates(&b), ge);
+            assert_eq!(b.dominates(&a), le);
+            assert_eq!(a.concurrent(&b), !le && !ge);
+            assert_eq!(b.concurrent(&a), !le && !ge);
+            assert_eq!(a.partial_cmp(&b), expected);
+            assert_eq!(b.partial_cmp(&a), expected.map(Ordering::reverse));
+            assert_eq!(a == b, a_model == b_model);
+
+            if expected.is_some() { ordered += 1 } else { concurrent += 1 }
+        }
+
+        // make sure both kinds of pair were actually exercised
+        assert!(ordered > 500 && concurrent > 500);
+    }
+
+    #[test]
+    fn happens_before_is_reflexive() {
+        let mut rng = Rng::new(0x6a09e667f3bcc908);
+        for _ in 0..1000 {
+            let (a, _) = random_clock(&mut rng, &empty());
+            assert!(a.happens_before(&a));
+            assert!(a.dominates(&a));
+            assert!(!a.concurrent(&a));
+            assert_eq!(a.partial_cmp(&a), Some(Ordering::Equal));
+        }
+    }
+
+    #[test]
+    fn witness_is_least_upper_bound() {
+        let mut rng = Rng::new(0xbb67ae8584caa73b);
+        for _ in 0..1000 {
+            let (a, a_model) = random_clock(&mut rng, &empty());
+            let (b, b_model) = random_clock(&mut rng, &(a.clone(), a_model));
+
+            let mut merged = a.clone();
+            merged.witness(&b);
+            for user in 0..USERS {
+                assert_eq!(merged.get(user as u16), a_model[user].max(b_model[user]));
+            }
+            assert!(a.happens_before(&merged));
+            assert!(b.happens_before(&merged));
+
+            let mut flipped = b.clone();
+            flipped.witness(&a);
+            assert_eq!(merged, flipped);
+
+            let mut again = merged.clone();
+            again.witness(&merged);
+            assert_eq!(again, merged);
+        }
+    }
+}
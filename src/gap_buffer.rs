@@ -0,0 +1,242 @@
+use crate::collection::Collection;
+
+/// A contiguous buffer with a gap of spare slots left at the last edit.
+/// Edits at the gap are O(1) amortized; moving the gap costs the distance moved.
+/// So sequential typing never shuffles the rest of the buffer around.
+///
+/// Slots in the gap hold `T::default()`, so the buffer stays a plain `Vec<T>`
+/// with no `Option` tag per slot and nothing uninitialised.
+/// That makes the bound `T: Clone + Default` rather than just `T: Clone`,
+/// so item types without a `Default` can't be stored.
+pub struct GapBuffer<T: Clone + Default> {
+    buffer:    Vec<T>,
+    gap_start: usize,
+    gap_end:   usize,
+}
+
+impl<T: Clone + Default> GapBuffer<T> {
+    pub fn new() -> Self {
+        GapBuffer { buffer: vec![], gap_start: 0, gap_end: 0 }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut buffer = GapBuffer::new();
+        buffer.grow(capacity);
+        buffer
+    }
+
+    fn gap_len(&self) -> usize {
+        self.gap_end - self.gap_start
+    }
+
+    /// Moves the gap so it starts at `pos`,
+    /// shifting only the items between the old and new gap.
+    fn move_gap(&mut self, pos: usize) {
+        if pos < self.gap_start {
+            let count = self.gap_start - pos;
+            for i in (0..count).rev() {
+                self.buffer.swap(pos + i, self.gap_end - count + i);
+            }
+            self.gap_start -= count;
+            self.gap_end -= count;
+        } else if pos > self.gap_start {
+            let count = pos - self.gap_start;
+            for i in 0..count {
+                self.buffer.swap(self.gap_start + i, self.gap_end + i);
+            }
+            self.gap_start += count;
+            self.gap_end += count;
+        }
+    }
+
+    /// Widens the gap so at least `needed` items fit,
+    /// doubling the buffer so repeated inserts stay amortized O(1).
+    fn grow(&mut self, needed: usize) {
+        if self.gap_len() >= needed { return; }
+        let extra = (needed - self.gap_len()).max(self.buffer.len());
+        self.buffer.splice(
+            self.gap_end..self.gap_end,
+            std::iter::repeat_with(T::default).take(extra),
+        );
+        self.gap_end += extra;
+    }
+
+    pub fn insert(&mut self, pos: usize, items: &[T]) {
+        assert!(pos <= self.len(), "insert position out of bounds");
+        self.move_gap(pos);
+        self.grow(items.len());
+        for item in items {
+            self.buffer[self.gap_start] = item.clone();
+            self.gap_start += 1;
+        }
+    }
+
+    /// Deletes by widening the gap over the deleted items,
+    /// resetting them so they don't hold on to anything.
+    pub fn delete(&mut self, pos: usize, len: usize) {
+        assert!(pos + len <= self.len(), "delete range out of bounds");
+        self.move_gap(pos);
+        for slot in self.buffer[self.gap_end..self.gap_end + len].iter_mut() {
+            *slot = T::default();
+        }
+        self.gap_end += len;
+    }
+
+    pub fn get(&self, pos: usize) -> Option<&T> {
+        if pos >= self.len() { return None; }
+        let index = if pos < self.gap_start { pos } else { pos + self.gap_len() };
+        Some(&self.buffer[index])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buffer[..self.gap_start].iter()
+            .chain(self.buffer[self.gap_end..].iter())
+    }
+}
+
+impl<T: Clone + Default> Default for GapBuffer<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Clone + Default> Collection for GapBuffer<T> {
+    fn len(&self) -> usize { self.buffer.len() - self.gap_len() }
+
+    /// Moves the gap to the split point first,
+    /// so both halves keep their gap at the end.
+    fn split(mut self, index: usize) -> (Self, Self) {
+        self.move_gap(index);
+        let after = self.buffer.split_off(self.gap_end);
+        let end = GapBuffer { gap_start: after.len(), gap_end: after.len(), buffer: after };
+        self.gap_end = self.buffer.len();
+        (self, end)
+    }
+
+    fn append(&mut self, mut other: Self) {
+        let len = self.len();
+        self.move_gap(len);
+        self.buffer.truncate(self.gap_start);
+
+        let tail = other.buffer.split_off(other.gap_end);
+        other.buffer.truncate(other.gap_start);
+        self.buffer.extend(other.buffer);
+        self.buffer.extend(tail);
+        self.gap_start = self.buffer.len();
+        self.gap_end = self.buffer.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+
+    fn contents(buffer: &GapBuffer<u8>) -> Vec<u8> {
+        buffer.iter().copied().collect()
+    }
+
+    #[test]
+    fn move_gap_both_ways() {
+        let mut buffer = GapBuffer::new();
+        buffer.insert(0, b"hello world");
+        assert_eq!(buffer.gap_start, 11);
+
+        // gap moves left, items before it shift right past the gap
+        buffer.insert(5, b",");
+        assert_eq!((buffer.gap_start, contents(&buffer)), (6, b"hello, world".to_vec()));
+        buffer.insert(0, b">");
+        assert_eq!((buffer.gap_start, contents(&buffer)), (1, b">hello, world".to_vec()));
+
+        // gap moves right, items after it shift left past the gap
+        buffer.insert(13, b"!");
+        assert_eq!((buffer.gap_start, contents(&buffer)), (14, b">hello, world!".to_vec()));
+        buffer.delete(6, 1);
+        assert_eq!((buffer.gap_start, contents(&buffer)), (6, b">hello world!".to_vec()));
+
+        for pos in 0..buffer.len() {
+            assert_eq!(buffer.get(pos), Some(&b">hello world!"[pos]));
+        }
+        assert_eq!(buffer.get(buffer.len()), None);
+    }
+
+    #[test]
+    fn grow_doubles_only_when_full() {
+        let mut buffer = GapBuffer::with_capacity(4);
+        assert_eq!(buffer.buffer.len(), 4);
+        buffer.insert(0, b"abcd");
+        assert_eq!(buffer.buffer.len(), 4);
+
+        buffer.insert(2, b"x");
+        assert_eq!(buffer.buffer.len(), 8);
+        assert_eq!(contents(&buffer), b"abxcd");
+
+        buffer.insert(5, b"0123456789");
+        assert_eq!(buffer.buffer.len(), 16);
+        assert_eq!(contents(&buffer), b"abxcd0123456789");
+    }
+
+    #[test]
+    fn delete_resets_gap_slots() {
+        let mut buffer = GapBuffer::new();
+        buffer.insert(0, &["a".to_string(), "b".to_string(), "c".to_string()]);
+        buffer.delete(1, 1);
+        assert_eq!(buffer.buffer[buffer.gap_start..buffer.gap_end], [String::new()]);
+        assert_eq!(buffer.iter().cloned().collect::<Vec<_>>(), ["a", "c"]);
+    }
+
+    #[test]
+    fn split_and_append() {
+        let mut buffer = GapBuffer::with_capacity(16);
+        buffer.insert(0, b"hello world");
+        buffer.insert(3, b"_");
+
+        let (left, right) = buffer.split(6);
+        assert_eq!(contents(&left), b"hel_lo");
+        assert_eq!(contents(&right), b" world");
+        assert_eq!((right.gap_start, right.gap_end), (6, 6));
+
+        let mut left = left;
+        left.insert(0, b"[");
+        let mut right = right;
+        right.insert(2, b"~");
+        left.append(right);
+        assert_eq!(contents(&left), b"[hel_lo w~orld");
+        assert_eq!(left.len(), 14);
+    }
+
+    #[test]
+    fn matches_vec() {
+        let mut rng = Rng::new(0x853c49e6748fea9b);
+
+        for _ in 0..200 {
+            let mut buffer = GapBuffer::new();
+            let mut model: Vec<u8> = vec![];
+
+            for _ in 0..60 {
+                match rng.below(4) {
+                    0 | 1 => {
+                        let pos = rng.below(model.len() + 1);
+                        let items: Vec<u8> = (0..rng.below(6)).map(|_| rng.below(256) as u8).collect();
+                        buffer.insert(pos, &items);
+                        model.splice(pos..pos, items);
+                    }
+                    2 => {
+                        let pos = rng.below(model.len() + 1);
+                        let len = rng.below(model.len() - pos + 1);
+                        buffer.delete(pos, len);
+                        model.drain(pos..pos + len);
+                    }
+                    _ => {
+                        let pos = rng.below(model.len() + 1);
+                        let (mut left, right) = buffer.split(pos);
+                        assert_eq!(contents(&left), model[..pos]);
+                        assert_eq!(contents(&right), model[pos..]);
+                        left.append(right);
+                        buffer = left;
+                    }
+                }
+                assert_eq!(buffer.len(), model.len());
+                assert_eq!(contents(&buffer), model);
+            }
+        }
+    }
+}
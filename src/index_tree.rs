@@ -99,4 +99,214 @@ impl<T> IndexTree<T> {
         left.append(right);
         *self = left;
     }
+
+    /// Returns an iterator over the items in `start..end`,
+    /// paired with their index in the tree.
+    /// Finding `start` costs the same as `get`, one `child_index` scan per level.
+    /// After that, siblings are stepped through lazily rather than copied out,
+    /// so each further item is amortized `O(1)` no matter how wide the nodes are.
+    pub fn range(&self, start: usize, end: usize) -> Range<'_, T> {
+        let mut stack = vec![];
+        let mut children = std::slice::from_ref(self);
+        let mut index = start;
+        let mut offset = 0;
+
+        loop {
+            let (before, i) = Self::child_index(children, index);
+            if i == children.len() { break; }
+            offset += before;
+            index -= before;
+
+            // remember where to pick up after the child we descend into
+            let child = &children[i];
+            stack.push((&children[i + 1..], offset + child.len()));
+
+            match child {
+                IndexTree::Leaf(_) => {
+                    stack.push((std::slice::from_ref(child), offset));
+                    break;
+                }
+                IndexTree::Node { children: inner, .. } => children = inner,
+            }
+        }
+
+        Range { stack, end }
+    }
+
+    /// Number of levels down to the deepest leaf, so a lone leaf has height 1
+    /// and a tree with no leaves, like `IndexTree::new()`, has height 0.
+    /// Nothing keeps the tree balanced, so this checks every path.
+    pub fn height(&self) -> usize {
+        match self {
+            IndexTree::Leaf(_) => 1,
+            IndexTree::Node { children, .. } => {
+                match children.iter().map(Self::height).max() {
+                    Some(height) if height > 0 => height + 1,
+                    _ => 0,
+                }
+            }
+        }
+    }
+
+    /// Number of internal nodes, including empty ones.
+    pub fn node_count(&self) -> usize {
+        match self {
+            IndexTree::Leaf(_) => 0,
+            IndexTree::Node { children, .. } => {
+                1 + children.iter().map(Self::node_count).sum::<usize>()
+            }
+        }
+    }
+
+    /// Number of leaves, counted by walking the tree
+    /// rather than trusting the cached sizes.
+    pub fn leaf_count(&self) -> usize {
+        match self {
+            IndexTree::Leaf(_) => 1,
+            IndexTree::Node { children, .. } => {
+                children.iter().map(Self::leaf_count).sum()
+            }
+        }
+    }
+
+    /// Returns whether all leaves sit at the same depth.
+    pub fn is_balanced(&self) -> bool {
+        self.leaf_depth(0, &mut None)
+    }
+
+    /// Checks every leaf against the depth of the first one found.
+    fn leaf_depth(&self, depth: usize, expected: &mut Option<usize>) -> bool {
+        match self {
+            IndexTree::Leaf(_) => *expected.get_or_insert(depth) == depth,
+            IndexTree::Node { children, .. } => {
+                children.iter().all(|child| child.leaf_depth(depth + 1, expected))
+            }
+        }
+    }
+}
+
+/// Iterator returned by `IndexTree::range`.
+/// Keeps a stack of cursors, each the remaining children at some level
+/// tagged with the index of the first item under them.
+pub struct Range<'a, T> {
+    stack: Vec<(&'a [IndexTree<T>], usize)>,
+    end: usize,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((children, offset)) = self.stack.pop() {
+            let (child, rest) = match children.split_first() {
+                Some(split) => split,
+                None => continue,
+            };
+            if offset >= self.end {
+                self.stack.clear();
+                return None;
+            }
+
+            self.stack.push((rest, offset + child.len()));
+            match child {
+                IndexTree::Leaf(item) => return Some((item, offset)),
+                IndexTree::Node { children, .. } => self.stack.push((children, offset)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+
+    /// Builds a random tree with uneven fanout and some empty nodes,
+    /// numbering leaves in order from `next`.
+    fn random_tree(rng: &mut Rng, depth: usize, next: &mut usize) -> IndexTree<usize> {
+        if depth == 0 || rng.below(4) == 0 {
+            *next += 1;
+            return IndexTree::Leaf(*next - 1);
+        }
+        let children = (0..rng.below(5))
+            .map(|_| random_tree(rng, depth - 1, next))
+            .collect();
+        IndexTree::new_from_children(children)
+    }
+
+    fn check_range(tree: &IndexTree<usize>, len: usize) {
+        for start in 0..=len + 1 {
+            for end in start..=len + 1 {
+                let got: Vec<_> = tree.range(start, end).map(|(item, i)| (*item, i)).collect();
+                let expected: Vec<_> = (start.min(len)..end.min(len)).map(|i| (i, i)).collect();
+                assert_eq!(got, expected, "range({}, {})", start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn range_matches_model() {
+        let mut rng = Rng::new(0x2545f4914f6cdd1d);
+        for _ in 0..200 {
+            let mut len = 0;
+            let tree = random_tree(&mut rng, 4, &mut len);
+            assert_eq!(tree.len(), len);
+            check_range(&tree, len);
+        }
+    }
+
+    #[test]
+    fn range_on_lone_leaf() {
+        check_range(&IndexTree::Leaf(0), 1);
+    }
+
+    #[test]
+    fn range_on_wide_node() {
+        let tree = IndexTree::new_from_children((0..100_000).map(IndexTree::Leaf).collect());
+        for start in [0, 1, 50_000, 99_999] {
+            let got: Vec<_> = tree.range(start, start + 2).map(|(item, i)| (*item, i)).collect();
+            let expected: Vec<_> = (start..(start + 2).min(100_000)).map(|i| (i, i)).collect();
+            assert_eq!(got, expected);
+        }
+    }
+
+    #[test]
+    fn diagnostics_on_unbalanced_tree() {
+        let tree = IndexTree::new_from_children(vec![
+            IndexTree::Leaf(0),
+            IndexTree::Leaf(1),
+            IndexTree::new_from_children(vec![IndexTree::Leaf(2)]),
+        ]);
+        assert!(!tree.is_balanced());
+        assert_eq!(tree.height(), 3);
+        assert_eq!(tree.node_count(), 2);
+        assert_eq!(tree.leaf_count(), 3);
+    }
+
+    #[test]
+    fn diagnostics_on_balanced_tree() {
+        let tree = IndexTree::new_from_children(vec![
+            IndexTree::new_from_children(vec![IndexTree::Leaf(0), IndexTree::Leaf(1)]),
+            IndexTree::new_from_children(vec![IndexTree::Leaf(2)]),
+        ]);
+        assert!(tree.is_balanced());
+        assert_eq!(tree.height(), 3);
+        assert_eq!(tree.node_count(), 3);
+        assert_eq!(tree.leaf_count(), tree.len());
+    }
+
+    #[test]
+    fn diagnostics_on_empty_tree() {
+        let tree: IndexTree<usize> = IndexTree::new();
+        assert!(tree.is_balanced());
+        assert_eq!(tree.height(), 0);
+        assert_eq!(tree.node_count(), 1);
+        assert_eq!(tree.leaf_count(), 0);
+
+        // empty nodes don't add to the height
+        let tree = IndexTree::new_from_children(vec![IndexTree::new(), IndexTree::Leaf(0)]);
+        assert_eq!(tree.height(), 2);
+        assert_eq!(IndexTree::Leaf(0).height(), 1);
+    }
 }
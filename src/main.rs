@@ -1,6 +1,13 @@
 mod collection;
 mod tree_log;
 mod index_tree;
+mod splay_tree;
+mod gap_buffer;
+mod clock;
+mod causal_graph;
+
+#[cfg(test)]
+mod test_rng;
 
 fn main() {
     println!("Testing...");
@@ -0,0 +1,343 @@
+use std::cmp::Ordering;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key:   K,
+    value: V,
+    left:  Link<K, V>,
+    right: Link<K, V>,
+}
+
+/// An ordered map that moves every node it touches to the root.
+/// Runs of nearby lookups stay near the top of the tree,
+/// which is what sequential editing looks like.
+pub struct SplayTree<K: Ord, V> {
+    root: Link<K, V>,
+    len:  usize,
+}
+
+impl<K: Ord, V> SplayTree<K, V> {
+    pub fn new() -> Self {
+        SplayTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut left = node.left.take().unwrap();
+        node.left = left.right.take();
+        left.right = Some(node);
+        left
+    }
+
+    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut right = node.right.take().unwrap();
+        node.right = right.left.take();
+        right.left = Some(node);
+        right
+    }
+
+    /// Top-down splay, so no recursion on degenerate trees.
+    /// Nodes smaller than the key are collected in `lefts`,
+    /// larger ones in `rights`, then hung back off the new root.
+    /// A zig-zig rotates before linking, a zig-zag just links twice.
+    fn splay_node(mut node: Box<Node<K, V>>, key: &K) -> Box<Node<K, V>> {
+        let mut lefts = vec![];
+        let mut rights = vec![];
+
+        loop {
+            match key.cmp(&node.key) {
+                Ordering::Equal => break,
+                Ordering::Less => {
+                    if node.left.is_none() { break; }
+                    if *key < node.left.as_ref().unwrap().key {
+                        node = Self::rotate_right(node);
+                        if node.left.is_none() { break; }
+                    }
+                    let next = node.left.take().unwrap();
+                    rights.push(node);
+                    node = next;
+                }
+                Ordering::Greater => {
+                    if node.right.is_none() { break; }
+                    if *key > node.right.as_ref().unwrap().key {
+                        node = Self::rotate_left(node);
+                        if node.right.is_none() { break; }
+                    }
+                    let next = node.right.take().unwrap();
+                    lefts.push(node);
+                    node = next;
+                }
+            }
+        }
+
+        let mut left = node.left.take();
+        for mut parent in lefts.into_iter().rev() {
+            parent.right = left;
+            left = Some(parent);
+        }
+
+        let mut right = node.right.take();
+        for mut parent in rights.into_iter().rev() {
+            parent.left = right;
+            right = Some(parent);
+        }
+
+        node.left = left;
+        node.right = right;
+        node
+    }
+
+    /// Rotates the node with `key` to the root.
+    /// If the key is missing, the last node on its search path ends up there instead.
+    pub fn splay(&mut self, key: &K) {
+        self.root = self.root.take().map(|root| Self::splay_node(root, key));
+    }
+
+    /// Inserts a key, returning the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.splay(&key);
+
+        let mut node = Box::new(Node { key, value, left: None, right: None });
+        match self.root.take() {
+            None => {}
+            Some(mut root) => match node.key.cmp(&root.key) {
+                Ordering::Equal => {
+                    let old = std::mem::replace(&mut root.value, node.value);
+                    self.root = Some(root);
+                    return Some(old);
+                }
+                Ordering::Less => {
+                    node.left = root.left.take();
+                    node.right = Some(root);
+                }
+                Ordering::Greater => {
+                    node.right = root.right.take();
+                    node.left = Some(root);
+                }
+            }
+        }
+
+        self.root = Some(node);
+        self.len += 1;
+        None
+    }
+
+    /// Looks up a key, splaying it to the root if found.
+    pub fn find(&mut self, key: &K) -> Option<&V> {
+        self.splay(key);
+        match &self.root {
+            Some(root) if root.key == *key => Some(&root.value),
+            _ => None,
+        }
+    }
+
+    pub fn find_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.splay(key);
+        match &mut self.root {
+            Some(root) if root.key == *key => Some(&mut root.value),
+            _ => None,
+        }
+    }
+
+    /// Removes a key and returns its value.
+    /// After splaying the key to the root, the largest node on the left
+    /// is splayed up to replace it, which leaves that node without a right child.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.splay(key);
+
+        let mut root = match self.root.take() {
+            Some(root) if root.key == *key => root,
+            other => { self.root = other; return None; }
+        };
+
+        self.root = match root.left.take() {
+            None => root.right.take(),
+            Some(left) => {
+                let mut left = Self::splay_node(left, key);
+                left.right = root.right.take();
+                Some(left)
+            }
+        };
+
+        self.len -= 1;
+        Some(root.value)
+    }
+
+    /// Iterates in key order without splaying.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+}
+
+impl<K: Ord, V> Default for SplayTree<K, V> {
+    fn default() -> Self { Self::new() }
+}
+
+/// Frees a subtree without recursing,
+/// as a splay tree can degrade into a long path.
+fn drop_link<K, V>(link: Link<K, V>) {
+    let mut stack: Vec<Box<Node<K, V>>> = link.into_iter().collect();
+    while let Some(mut node) = stack.pop() {
+        stack.extend(node.left.take());
+        stack.extend(node.right.take());
+    }
+}
+
+impl<K: Ord, V> Drop for SplayTree<K, V> {
+    fn drop(&mut self) {
+        drop_link(self.root.take());
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(next) = node {
+            self.stack.push(next);
+            node = next.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a SplayTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Owning iterator, nodes are detached from the left spine as they're visited.
+pub struct IntoIter<K, V> {
+    stack: Vec<Box<Node<K, V>>>,
+}
+
+impl<K, V> IntoIter<K, V> {
+    fn push_left(&mut self, mut link: Link<K, V>) {
+        while let Some(mut node) = link {
+            link = node.left.take();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.push_left(node.right.take());
+        let Node { key, value, .. } = *node;
+        Some((key, value))
+    }
+}
+
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        for node in self.stack.drain(..) {
+            drop_link(Some(node));
+        }
+    }
+}
+
+impl<K: Ord, V> IntoIterator for SplayTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut iter = IntoIter { stack: vec![] };
+        iter.push_left(self.root.take());
+        iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = Rng::new(0x9e3779b97f4a7c15);
+        for _ in 0..100 {
+            let mut tree = SplayTree::new();
+            let mut model = BTreeMap::new();
+
+            for _ in 0..300 {
+                let key = rng.below(100);
+                match rng.below(5) {
+                    0 | 1 => {
+                        let value = rng.below(1000);
+                        assert_eq!(tree.insert(key, value), model.insert(key, value));
+                    }
+                    2 => assert_eq!(tree.remove(&key), model.remove(&key)),
+                    3 => {
+                        if let Some(value) = tree.find_mut(&key) { *value += 1; }
+                        if let Some(value) = model.get_mut(&key) { *value += 1; }
+                    }
+                    _ => assert_eq!(tree.find(&key), model.get(&key)),
+                }
+                assert_eq!(tree.len(), model.len());
+                assert_eq!(tree.is_empty(), model.is_empty());
+            }
+
+            let borrowed: Vec<_> = tree.iter().collect();
+            assert_eq!(borrowed, model.iter().collect::<Vec<_>>());
+
+            let owned: Vec<_> = tree.into_iter().collect();
+            assert_eq!(owned, model.into_iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn find_splays_to_root() {
+        let mut tree = SplayTree::new();
+        for key in 0..10 { tree.insert(key, ()); }
+        tree.find(&3);
+        assert_eq!(tree.root.as_ref().unwrap().key, 3);
+        tree.splay(&7);
+        assert_eq!(tree.root.as_ref().unwrap().key, 7);
+    }
+
+    #[test]
+    fn partly_used_into_iter_drops() {
+        let mut tree = SplayTree::new();
+        for key in 0..1000 { tree.insert(key, key.to_string()); }
+        let mut iter = tree.into_iter();
+        assert_eq!(iter.next(), Some((0, "0".to_string())));
+        assert_eq!(iter.next(), Some((1, "1".to_string())));
+        drop(iter);
+    }
+
+    /// Sequential inserts leave a path as long as the tree,
+    /// so splaying and dropping must not recurse.
+    #[test]
+    fn degenerate_sequential_inserts() {
+        let mut tree = SplayTree::new();
+        for key in 0..200_000u32 { tree.insert(key, key); }
+        assert_eq!(tree.find(&0), Some(&0));
+        assert_eq!(tree.find(&199_999), Some(&199_999));
+        assert_eq!(tree.remove(&100_000), Some(100_000));
+        assert_eq!(tree.len(), 199_999);
+        assert!(tree.iter().map(|(key, _)| *key).eq((0..200_000).filter(|key| *key != 100_000)));
+    }
+}
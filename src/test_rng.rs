@@ -0,0 +1,25 @@
+/// A tiny xorshift generator shared by the test modules,
+/// so randomized tests are reproducible without pulling in `rand`.
+pub struct Rng(u64);
+
+impl Rng {
+    /// The seed must be nonzero, or every output is zero.
+    pub fn new(seed: u64) -> Self {
+        assert!(seed != 0, "xorshift seed must be nonzero");
+        Rng(seed)
+    }
+
+    /// Returns a value in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}